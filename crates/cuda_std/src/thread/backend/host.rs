@@ -0,0 +1,103 @@
+//! Fallback used for any target that is neither NVPTX nor AMDGPU.
+//!
+//! The public wrappers in [`super::super`] aren't individually `#[gpu_only]`-gated, so they
+//! need *something* to call here even off-device - this is what keeps `cargo check`/`cargo
+//! doc` working on the host. Every function is itself `#[gpu_only]`, the same panic-on-host
+//! idiom used by every other GPU-only intrinsic wrapper in this crate, rather than a
+//! one-off `unimplemented!()`.
+
+use cuda_std_macros::gpu_only;
+
+#[gpu_only]
+#[inline(always)]
+pub(crate) unsafe fn thread_idx_x() -> u32 {
+    0
+}
+
+#[gpu_only]
+#[inline(always)]
+pub(crate) unsafe fn thread_idx_y() -> u32 {
+    0
+}
+
+#[gpu_only]
+#[inline(always)]
+pub(crate) unsafe fn thread_idx_z() -> u32 {
+    0
+}
+
+#[gpu_only]
+#[inline(always)]
+pub(crate) unsafe fn block_idx_x() -> u32 {
+    0
+}
+
+#[gpu_only]
+#[inline(always)]
+pub(crate) unsafe fn block_idx_y() -> u32 {
+    0
+}
+
+#[gpu_only]
+#[inline(always)]
+pub(crate) unsafe fn block_idx_z() -> u32 {
+    0
+}
+
+#[gpu_only]
+#[inline(always)]
+pub(crate) unsafe fn block_dim_x() -> u32 {
+    0
+}
+
+#[gpu_only]
+#[inline(always)]
+pub(crate) unsafe fn block_dim_y() -> u32 {
+    0
+}
+
+#[gpu_only]
+#[inline(always)]
+pub(crate) unsafe fn block_dim_z() -> u32 {
+    0
+}
+
+#[gpu_only]
+#[inline(always)]
+pub(crate) unsafe fn grid_dim_x() -> u32 {
+    0
+}
+
+#[gpu_only]
+#[inline(always)]
+pub(crate) unsafe fn grid_dim_y() -> u32 {
+    0
+}
+
+#[gpu_only]
+#[inline(always)]
+pub(crate) unsafe fn grid_dim_z() -> u32 {
+    0
+}
+
+#[gpu_only]
+#[inline(always)]
+pub(crate) unsafe fn warp_size() -> u32 {
+    0
+}
+
+#[gpu_only]
+#[inline(always)]
+pub(crate) unsafe fn block_barrier() {}
+
+#[gpu_only]
+#[inline(always)]
+pub(crate) unsafe fn grid_fence() {}
+
+#[gpu_only]
+#[inline(always)]
+pub(crate) unsafe fn device_fence() {}
+
+#[gpu_only]
+#[inline(always)]
+pub(crate) unsafe fn system_fence() {}
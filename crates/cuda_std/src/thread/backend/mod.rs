@@ -0,0 +1,26 @@
+//! Raw, architecture-specific intrinsics backing the thread model in [`super`].
+//!
+//! The public functions in [`super`] (`thread_idx`, `block_idx`, `block_dim`, `grid_dim`,
+//! `sync_threads`, `warp_size`, the fences, ...) are backend-agnostic; this module picks
+//! the right raw compiler intrinsics for the target being compiled for, so kernel code
+//! written against [`super`] is source-compatible across backends.
+
+#[cfg(target_arch = "nvptx64")]
+mod nvptx;
+#[cfg(target_arch = "nvptx64")]
+pub(crate) use nvptx::*;
+
+#[cfg(target_arch = "amdgpu")]
+mod amdgpu;
+#[cfg(target_arch = "amdgpu")]
+pub(crate) use amdgpu::*;
+
+// Neither NVPTX nor AMDGPU: this is a host build (`cargo check`/`cargo doc`/docs.rs, or a
+// kernel crate's host-side build script dependency). The wrappers in `super` call straight
+// into `backend::*` without a `#[gpu_only]` gate, so this arm exists purely to keep the
+// crate compiling there - every function in it panics unconditionally and is never meant
+// to actually run.
+#[cfg(not(any(target_arch = "nvptx64", target_arch = "amdgpu")))]
+mod host;
+#[cfg(not(any(target_arch = "nvptx64", target_arch = "amdgpu")))]
+pub(crate) use host::*;
@@ -0,0 +1,113 @@
+//! NVPTX intrinsics, as exposed by `libintrinsics.ll`.
+
+// different calling conventions dont exist in nvptx, so we just use C as a placeholder.
+extern "C" {
+    fn __nvvm_thread_idx_x() -> u32;
+    fn __nvvm_thread_idx_y() -> u32;
+    fn __nvvm_thread_idx_z() -> u32;
+
+    fn __nvvm_block_dim_x() -> u32;
+    fn __nvvm_block_dim_y() -> u32;
+    fn __nvvm_block_dim_z() -> u32;
+
+    fn __nvvm_block_idx_x() -> u32;
+    fn __nvvm_block_idx_y() -> u32;
+    fn __nvvm_block_idx_z() -> u32;
+
+    fn __nvvm_grid_dim_x() -> u32;
+    fn __nvvm_grid_dim_y() -> u32;
+    fn __nvvm_grid_dim_z() -> u32;
+
+    fn __nvvm_warp_size() -> u32;
+
+    fn __nvvm_block_barrier();
+
+    fn __nvvm_grid_fence();
+    fn __nvvm_device_fence();
+    fn __nvvm_system_fence();
+}
+
+#[inline(always)]
+pub(crate) unsafe fn thread_idx_x() -> u32 {
+    __nvvm_thread_idx_x()
+}
+
+#[inline(always)]
+pub(crate) unsafe fn thread_idx_y() -> u32 {
+    __nvvm_thread_idx_y()
+}
+
+#[inline(always)]
+pub(crate) unsafe fn thread_idx_z() -> u32 {
+    __nvvm_thread_idx_z()
+}
+
+#[inline(always)]
+pub(crate) unsafe fn block_idx_x() -> u32 {
+    __nvvm_block_idx_x()
+}
+
+#[inline(always)]
+pub(crate) unsafe fn block_idx_y() -> u32 {
+    __nvvm_block_idx_y()
+}
+
+#[inline(always)]
+pub(crate) unsafe fn block_idx_z() -> u32 {
+    __nvvm_block_idx_z()
+}
+
+#[inline(always)]
+pub(crate) unsafe fn block_dim_x() -> u32 {
+    __nvvm_block_dim_x()
+}
+
+#[inline(always)]
+pub(crate) unsafe fn block_dim_y() -> u32 {
+    __nvvm_block_dim_y()
+}
+
+#[inline(always)]
+pub(crate) unsafe fn block_dim_z() -> u32 {
+    __nvvm_block_dim_z()
+}
+
+#[inline(always)]
+pub(crate) unsafe fn grid_dim_x() -> u32 {
+    __nvvm_grid_dim_x()
+}
+
+#[inline(always)]
+pub(crate) unsafe fn grid_dim_y() -> u32 {
+    __nvvm_grid_dim_y()
+}
+
+#[inline(always)]
+pub(crate) unsafe fn grid_dim_z() -> u32 {
+    __nvvm_grid_dim_z()
+}
+
+#[inline(always)]
+pub(crate) unsafe fn warp_size() -> u32 {
+    __nvvm_warp_size()
+}
+
+#[inline(always)]
+pub(crate) unsafe fn block_barrier() {
+    __nvvm_block_barrier()
+}
+
+#[inline(always)]
+pub(crate) unsafe fn grid_fence() {
+    __nvvm_grid_fence()
+}
+
+#[inline(always)]
+pub(crate) unsafe fn device_fence() {
+    __nvvm_device_fence()
+}
+
+#[inline(always)]
+pub(crate) unsafe fn system_fence() {
+    __nvvm_system_fence()
+}
@@ -0,0 +1,115 @@
+//! Division by a runtime-constant divisor without hardware division.
+//!
+//! 32-bit integer division and modulo are notably slow on the GPU. When the same divisor
+//! is reused many times (e.g. the width of a conceptually 2d/3d domain that a kernel
+//! iterates over via a flat, 1d [`super::index`]), [`FastDivMod`] replaces the division
+//! with a multiply-high and a shift, computed once up front.
+
+/// A divisor `d` precomputed so that dividing by it on the device costs a multiply-high
+/// and a shift instead of a real `div`/`mod`.
+///
+/// Construction is cheap enough to do once per kernel launch, or ahead of time on the
+/// host, since it's just a handful of scalar operations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FastDivMod {
+    divisor: u32,
+    multiplier: u32,
+    shift: u32,
+}
+
+impl FastDivMod {
+    /// Precomputes a [`FastDivMod`] for dividing by `d`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `d` is `0`.
+    pub fn new(d: u32) -> Self {
+        assert!(d > 0, "FastDivMod divisor must be non-zero");
+
+        // Done in `u64` since the smallest valid `shift` for `d > 2^31` is `32`, and
+        // `1u32 << 32` would either panic (overflow checks) or wrap back to `0` (release),
+        // looping forever.
+        let mut shift = 0u32;
+        while (1u64 << shift) < d as u64 {
+            shift += 1;
+        }
+
+        let multiplier = ((1u64 << 32) * ((1u64 << shift) - d as u64) / d as u64 + 1) as u32;
+
+        Self {
+            divisor: d,
+            multiplier,
+            shift,
+        }
+    }
+
+    /// Returns `n / d` for the divisor `d` this was constructed with.
+    ///
+    /// The multiply-high `mulhi(n, multiplier) + n` step can itself overflow 32 bits, so
+    /// the intermediate is carried in 64 bits until after the final shift - truncating it
+    /// back to 32 bits first would silently drop that carry and corrupt the quotient for
+    /// large `n`.
+    #[inline(always)]
+    pub fn div(&self, n: u32) -> u32 {
+        let mulhi = ((n as u64 * self.multiplier as u64) >> 32) as u32;
+        (((mulhi as u64) + (n as u64)) >> self.shift) as u32
+    }
+
+    /// Returns `n % d` for the divisor `d` this was constructed with.
+    #[inline(always)]
+    pub fn modulo(&self, n: u32) -> u32 {
+        n - self.div(n) * self.divisor
+    }
+
+    /// Returns `(n / d, n % d)` for the divisor `d` this was constructed with, computing
+    /// only a single division.
+    #[inline(always)]
+    pub fn div_mod(&self, n: u32) -> (u32, u32) {
+        let q = self.div(n);
+        (q, n - q * self.divisor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FastDivMod;
+
+    #[test]
+    fn matches_hardware_division_across_the_full_u32_range() {
+        for d in [
+            1u32,
+            2,
+            3,
+            5,
+            7,
+            10,
+            16,
+            255,
+            4096,
+            1_000_000_007,
+            // Divisors above `2^31` need `shift == 32`, which is what overflowed the
+            // `u32`-typed shift search.
+            2_147_483_649,
+            3_000_000_000,
+            u32::MAX - 1,
+            u32::MAX,
+        ] {
+            let fast = FastDivMod::new(d);
+            for n in [
+                0u32,
+                1,
+                d.wrapping_sub(1),
+                d,
+                d.wrapping_add(1),
+                1 << 31,
+                (1u32 << 31).wrapping_add(d),
+                u32::MAX - 1,
+                u32::MAX,
+            ] {
+                assert_eq!(fast.div(n), n / d, "div({n}, {d})");
+                assert_eq!(fast.modulo(n), n % d, "modulo({n}, {d})");
+                assert_eq!(fast.div_mod(n), (n / d, n % d), "div_mod({n}, {d})");
+            }
+        }
+    }
+}
@@ -0,0 +1,284 @@
+//! Grid-wide (cooperative) synchronization.
+//!
+//! [`super::grid_fence`] only fences memory - it does not stop a block from racing ahead
+//! of another block, so on its own it cannot be used to implement algorithms that need
+//! every block to reach a point before any of them continues. [`GridBarrier`] provides
+//! that stronger guarantee, at the cost of requiring the kernel to be launched
+//! cooperatively, so that the driver guarantees every participating block is resident on
+//! the device at the same time.
+
+use cuda_std_macros::{gpu_only, shared_array};
+use vek::Vec3;
+
+use super::warp::{self, WarpShuffle};
+use super::{block_dim, block_idx, device_fence, grid_dim, nanosleep, sync_threads, thread_idx};
+
+const PHASE_BIT: u64 = 1 << 63;
+
+/// A grid-wide barrier built on top of a single `u64` semaphore shared by every
+/// participating block.
+///
+/// `semaphore` must be initialized to `0` before the first [`GridBarrier::wait`] call.
+/// Every participating block must construct a `GridBarrier` with the same `semaphore`
+/// pointer and `participating` block count, and must call [`wait`](GridBarrier::wait)
+/// the same number of times. This is what makes it safe to reuse for a barrier called
+/// repeatedly in a loop: each call flips the high bit of `semaphore` instead of resetting
+/// it back to `0`, so even and odd phases wait on different target values without needing
+/// a second barrier just to reset the counter.
+///
+/// Exactly one block per barrier group must be constructed with `is_segment_leader` set
+/// to `true` - this is the block responsible for flipping the phase bit once every other
+/// block in the group has arrived. Grid-wide barriers are just the special case where
+/// `participating` is the whole grid; a subset of blocks along chosen grid dimensions can
+/// form an independent barrier group by giving that group its own `semaphore` and
+/// `participating` count.
+pub struct GridBarrier {
+    semaphore: *mut u64,
+    participating: u32,
+    is_segment_leader: bool,
+}
+
+impl GridBarrier {
+    /// Creates a new grid barrier over `participating` blocks, synchronized through
+    /// `semaphore`.
+    #[inline(always)]
+    pub fn new(semaphore: *mut u64, participating: u32, is_segment_leader: bool) -> Self {
+        Self {
+            semaphore,
+            participating,
+            is_segment_leader,
+        }
+    }
+
+    /// Blocks the calling thread block until every other participating block has also
+    /// called `wait`, and makes any global memory writes from before the call visible to
+    /// every participating block after it returns.
+    ///
+    /// Can be called repeatedly (e.g. once per iteration of a loop) without resetting
+    /// `semaphore` in between calls.
+    #[inline(always)]
+    pub fn wait(&mut self) {
+        sync_threads();
+
+        if thread_idx() == Vec3::zero() {
+            device_fence();
+
+            // Every block contributes `1`, except the segment leader, which instead
+            // contributes exactly the amount needed to flip the phase bit the moment the
+            // low bits reach `participating`.
+            let increment: u64 = if self.is_segment_leader {
+                PHASE_BIT.wrapping_sub(self.participating as u64 - 1)
+            } else {
+                1
+            };
+
+            let before = unsafe { atomic_add_u64(self.semaphore, increment) };
+            let target_phase_bit = (before & PHASE_BIT) ^ PHASE_BIT;
+
+            let mut backoff = 32;
+            while unsafe { core::ptr::read_volatile(self.semaphore) } & PHASE_BIT != target_phase_bit {
+                nanosleep(backoff);
+                backoff = (backoff * 2).min(1024);
+            }
+        }
+
+        sync_threads();
+    }
+}
+
+/// Synchronizes all `participating` blocks in a cooperatively-launched kernel at this
+/// single point.
+///
+/// Equivalent to constructing a [`GridBarrier`] and calling [`GridBarrier::wait`] once;
+/// prefer [`GridBarrier`] directly for a barrier that is waited on more than once, so the
+/// phase-flipping state doesn't have to be reconstructed every call.
+#[inline(always)]
+pub fn grid_barrier(semaphore: *mut u64, participating: u32, is_segment_leader: bool) {
+    GridBarrier::new(semaphore, participating, is_segment_leader).wait();
+}
+
+#[gpu_only]
+#[inline(always)]
+unsafe fn atomic_add_u64(ptr: *mut u64, val: u64) -> u64 {
+    let old: u64;
+    asm!(
+        "atom.global.add.u64 {0}, [{1}], {2};",
+        out(reg64) old,
+        in(reg64) ptr,
+        in(reg64) val,
+    );
+    old
+}
+
+/// Selects which of the 3 dimensions of the grid or block a [`grid_reduce`] call should
+/// treat as "the same group" - either which grid dimensions collapse into the same
+/// reduction segment, or which block dimensions actually hold a distinct thread-private
+/// input to reduce.
+#[derive(Clone, Copy, Default)]
+pub struct DimMask {
+    pub x: bool,
+    pub y: bool,
+    pub z: bool,
+}
+
+impl DimMask {
+    /// All three dimensions selected, e.g. a single reduction segment spanning the whole
+    /// grid, or every thread in a block holding a distinct input.
+    pub const ALL: Self = Self {
+        x: true,
+        y: true,
+        z: true,
+    };
+
+    /// No dimensions selected, e.g. one reduction segment per block, or only thread
+    /// `(0, 0, 0)` of each block holding a meaningful input.
+    pub const NONE: Self = Self {
+        x: false,
+        y: false,
+        z: false,
+    };
+}
+
+const MAX_WARPS_PER_BLOCK: usize = 32;
+
+/// Reduces `input` across a reduction segment of the grid using `op`, writing the result
+/// of each segment to a distinct slot of `out`.
+///
+/// `segment` selects which grid dimensions collapse into the same segment: all three set
+/// gives a single, grid-wide reduction; all three clear reduces each block independently
+/// (no cross-block work at all). `thread_participation` selects which block dimensions
+/// hold a distinct thread-private input: for a dimension left unset, only the thread at
+/// index `0` along that dimension contributes its `input`, letting callers reduce either
+/// every lane or just one representative lane per block.
+///
+/// `work_buf` must have room for one `T` per block in the grid, and `out` must have room
+/// for one `T` per reduction segment; `semaphore` must have room for one `u64` per
+/// reduction segment and must be zeroed before launch. This function requires the kernel
+/// to have been launched cooperatively, since it is built on top of [`GridBarrier`].
+#[inline(always)]
+pub fn grid_reduce<T: WarpShuffle>(
+    input: T,
+    out: *mut T,
+    work_buf: *mut T,
+    semaphore: *mut u64,
+    segment: DimMask,
+    thread_participation: DimMask,
+    op: impl Fn(T, T) -> T + Copy,
+) {
+    let tid = thread_idx();
+    let bdim = block_dim();
+    let gdim = grid_dim();
+    let bidx = block_idx();
+
+    let participates = (thread_participation.x || tid.x == 0)
+        && (thread_participation.y || tid.y == 0)
+        && (thread_participation.z || tid.z == 0);
+
+    let lane_mask = warp::ballot_sync(warp::FULL_MASK, participates);
+
+    // Only the lowest active lane per warp writes that warp's partial into shared memory,
+    // and only warps that actually had at least one participating lane get combined below.
+    let scratch = unsafe { shared_array![u32; MAX_WARPS_PER_BLOCK] };
+    let contributed = unsafe { shared_array![u32; MAX_WARPS_PER_BLOCK] };
+
+    let linear_tid = tid.z * (bdim.x * bdim.y) + tid.y * bdim.x + tid.x;
+    let warp_id = linear_tid / super::warp_size();
+
+    if linear_tid < MAX_WARPS_PER_BLOCK {
+        unsafe { *contributed.add(linear_tid) = 0 };
+    }
+    sync_threads();
+
+    if participates {
+        let reduced = warp::warp_reduce(lane_mask, input, op);
+        if warp::lane_id() == lane_mask.trailing_zeros() {
+            unsafe {
+                *scratch.add(warp_id) = reduced.to_warp_bits();
+                *contributed.add(warp_id) = 1;
+            }
+        }
+    }
+    sync_threads();
+
+    if linear_tid == 0 {
+        let warps_in_block = (bdim.x * bdim.y * bdim.z + super::warp_size() - 1) / super::warp_size();
+        let mut acc: Option<T> = None;
+        for w in 0..warps_in_block.min(MAX_WARPS_PER_BLOCK) {
+            if unsafe { *contributed.add(w) } != 0 {
+                let partial = T::from_warp_bits(unsafe { *scratch.add(w) });
+                acc = Some(match acc {
+                    Some(prev) => op(prev, partial),
+                    None => partial,
+                });
+            }
+        }
+
+        // `thread_participation` should always let at least one thread per block
+        // through, so every block has a partial to contribute to its segment.
+        if let Some(block_partial) = acc {
+            let block_id = bidx.x + bidx.y * gdim.x + bidx.z * gdim.x * gdim.y;
+            unsafe { *work_buf.add(block_id) = block_partial };
+        }
+    }
+
+    // These are derived purely from `block_idx`/`grid_dim`, so every thread in the block
+    // computes the same values - that's what lets every thread call `GridBarrier::wait`
+    // (which itself calls `sync_threads`) without diverging.
+    let segment_coord = Vec3::new(
+        if segment.x { 0 } else { bidx.x },
+        if segment.y { 0 } else { bidx.y },
+        if segment.z { 0 } else { bidx.z },
+    );
+
+    // Linearized over only the dimensions that *aren't* collapsed into the segment (a
+    // collapsed dimension always contributes coordinate `0`, above, so it must also
+    // contribute size `1` here) - otherwise, e.g. `segment = {x: true, y: false, z: false}`
+    // would linearize with a stride of the *full* `gdim.x` despite there being only one
+    // live `x` coordinate, leaving gaps and writing past `gdim.y * gdim.z` segments' worth
+    // of `out`/`semaphore`.
+    let segment_dim_x = if segment.x { 1 } else { gdim.x };
+    let segment_dim_y = if segment.y { 1 } else { gdim.y };
+    let segment_index =
+        segment_coord.x + segment_coord.y * segment_dim_x + segment_coord.z * segment_dim_x * segment_dim_y;
+
+    let segment_size = (if segment.x { gdim.x } else { 1 })
+        * (if segment.y { gdim.y } else { 1 })
+        * (if segment.z { gdim.z } else { 1 });
+    let is_segment_leader = (!segment.x || bidx.x == 0)
+        && (!segment.y || bidx.y == 0)
+        && (!segment.z || bidx.z == 0);
+    let is_last_in_segment = (!segment.x || bidx.x == gdim.x - 1)
+        && (!segment.y || bidx.y == gdim.y - 1)
+        && (!segment.z || bidx.z == gdim.z - 1);
+
+    let mut barrier = GridBarrier::new(
+        unsafe { semaphore.add(segment_index) },
+        segment_size as u32,
+        is_segment_leader,
+    );
+    barrier.wait();
+
+    if is_last_in_segment && linear_tid == 0 {
+        let x_range = if segment.x { 0..gdim.x } else { bidx.x..bidx.x + 1 };
+        let y_range = if segment.y { 0..gdim.y } else { bidx.y..bidx.y + 1 };
+        let z_range = if segment.z { 0..gdim.z } else { bidx.z..bidx.z + 1 };
+
+        let mut final_acc: Option<T> = None;
+        for z in z_range.clone() {
+            for y in y_range.clone() {
+                for x in x_range.clone() {
+                    let id = x + y * gdim.x + z * gdim.x * gdim.y;
+                    let val = unsafe { *work_buf.add(id) };
+                    final_acc = Some(match final_acc {
+                        Some(prev) => op(prev, val),
+                        None => val,
+                    });
+                }
+            }
+        }
+
+        if let Some(result) = final_acc {
+            unsafe { *out.add(segment_index) = result };
+        }
+    }
+}
@@ -18,95 +18,75 @@
 
 // TODO: write some docs about the terms used in this module.
 
+mod backend;
+pub mod fast_div;
+pub mod grid;
+pub mod vector;
+pub mod warp;
+
 use cuda_std_macros::gpu_only;
 use vek::{Vec2, Vec3};
 
-// different calling conventions dont exist in nvptx, so we just use C as a placeholder.
-extern "C" {
-    // defined in libintrinsics.ll
-    fn __nvvm_thread_idx_x() -> u32;
-    fn __nvvm_thread_idx_y() -> u32;
-    fn __nvvm_thread_idx_z() -> u32;
-
-    fn __nvvm_block_dim_x() -> u32;
-    fn __nvvm_block_dim_y() -> u32;
-    fn __nvvm_block_dim_z() -> u32;
-
-    fn __nvvm_block_idx_x() -> u32;
-    fn __nvvm_block_idx_y() -> u32;
-    fn __nvvm_block_idx_z() -> u32;
-
-    fn __nvvm_grid_dim_x() -> u32;
-    fn __nvvm_grid_dim_y() -> u32;
-    fn __nvvm_grid_dim_z() -> u32;
-
-    fn __nvvm_warp_size() -> u32;
-
-    fn __nvvm_block_barrier();
-
-    fn __nvvm_grid_fence();
-    fn __nvvm_device_fence();
-    fn __nvvm_system_fence();
-}
+use fast_div::FastDivMod;
 
 #[inline(always)]
 pub fn thread_idx_x() -> usize {
-    unsafe { __nvvm_thread_idx_x() as usize }
+    unsafe { backend::thread_idx_x() as usize }
 }
 
 #[inline(always)]
 pub fn thread_idx_y() -> usize {
-    unsafe { __nvvm_thread_idx_y() as usize }
+    unsafe { backend::thread_idx_y() as usize }
 }
 
 #[inline(always)]
 pub fn thread_idx_z() -> usize {
-    unsafe { __nvvm_thread_idx_z() as usize }
+    unsafe { backend::thread_idx_z() as usize }
 }
 
 #[inline(always)]
 pub fn block_idx_x() -> usize {
-    unsafe { __nvvm_block_idx_x() as usize }
+    unsafe { backend::block_idx_x() as usize }
 }
 
 #[inline(always)]
 pub fn block_idx_y() -> usize {
-    unsafe { __nvvm_block_idx_y() as usize }
+    unsafe { backend::block_idx_y() as usize }
 }
 
 #[inline(always)]
 pub fn block_idx_z() -> usize {
-    unsafe { __nvvm_block_idx_z() as usize }
+    unsafe { backend::block_idx_z() as usize }
 }
 
 #[inline(always)]
 pub fn block_dim_x() -> usize {
-    unsafe { __nvvm_block_dim_x() as usize }
+    unsafe { backend::block_dim_x() as usize }
 }
 
 #[inline(always)]
 pub fn block_dim_y() -> usize {
-    unsafe { __nvvm_block_dim_y() as usize }
+    unsafe { backend::block_dim_y() as usize }
 }
 
 #[inline(always)]
 pub fn block_dim_z() -> usize {
-    unsafe { __nvvm_block_dim_z() as usize }
+    unsafe { backend::block_dim_z() as usize }
 }
 
 #[inline(always)]
 pub fn grid_dim_x() -> usize {
-    unsafe { __nvvm_grid_dim_x() as usize }
+    unsafe { backend::grid_dim_x() as usize }
 }
 
 #[inline(always)]
 pub fn grid_dim_y() -> usize {
-    unsafe { __nvvm_grid_dim_y() as usize }
+    unsafe { backend::grid_dim_y() as usize }
 }
 
 #[inline(always)]
 pub fn grid_dim_z() -> usize {
-    unsafe { __nvvm_grid_dim_z() as usize }
+    unsafe { backend::grid_dim_z() as usize }
 }
 
 /// Gets the 3d index of the thread currently executing the kernel.
@@ -114,9 +94,9 @@ pub fn grid_dim_z() -> usize {
 pub fn thread_idx() -> Vec3<usize> {
     unsafe {
         Vec3::new(
-            __nvvm_thread_idx_x() as usize,
-            __nvvm_thread_idx_y() as usize,
-            __nvvm_thread_idx_z() as usize,
+            backend::thread_idx_x() as usize,
+            backend::thread_idx_y() as usize,
+            backend::thread_idx_z() as usize,
         )
     }
 }
@@ -126,9 +106,9 @@ pub fn thread_idx() -> Vec3<usize> {
 pub fn block_idx() -> Vec3<usize> {
     unsafe {
         Vec3::new(
-            __nvvm_block_idx_x() as usize,
-            __nvvm_block_idx_y() as usize,
-            __nvvm_block_idx_z() as usize,
+            backend::block_idx_x() as usize,
+            backend::block_idx_y() as usize,
+            backend::block_idx_z() as usize,
         )
     }
 }
@@ -139,9 +119,9 @@ pub fn block_idx() -> Vec3<usize> {
 pub fn block_dim() -> Vec3<usize> {
     unsafe {
         Vec3::new(
-            __nvvm_block_dim_x() as usize,
-            __nvvm_block_dim_y() as usize,
-            __nvvm_block_dim_z() as usize,
+            backend::block_dim_x() as usize,
+            backend::block_dim_y() as usize,
+            backend::block_dim_z() as usize,
         )
     }
 }
@@ -152,9 +132,9 @@ pub fn block_dim() -> Vec3<usize> {
 pub fn grid_dim() -> Vec3<usize> {
     unsafe {
         Vec3::new(
-            __nvvm_grid_dim_x() as usize,
-            __nvvm_grid_dim_y() as usize,
-            __nvvm_grid_dim_z() as usize,
+            backend::grid_dim_x() as usize,
+            backend::grid_dim_y() as usize,
+            backend::grid_dim_z() as usize,
         )
     }
 }
@@ -196,6 +176,27 @@ pub fn index_3d() -> Vec3<usize> {
     Vec3::new(i, j, k)
 }
 
+/// Decomposes the flat, 1d [`index`] into a 2d coordinate using a precomputed
+/// [`FastDivMod`] for `width`, avoiding a hardware division.
+///
+/// This is for kernels that are launched with a simple 1d grid/block configuration but
+/// conceptually iterate over a 2d domain of the given `width`, e.g. processing a
+/// row-major image or matrix one linear thread per element.
+#[inline(always)]
+pub fn index_2d_fast(width: &FastDivMod) -> Vec2<usize> {
+    let (y, x) = width.div_mod(index() as u32);
+    Vec2::new(x as usize, y as usize)
+}
+
+/// Decomposes the flat, 1d [`index`] into a 3d coordinate using precomputed
+/// [`FastDivMod`]s for `width` and `height`, avoiding two hardware divisions.
+#[inline(always)]
+pub fn index_3d_fast(width: &FastDivMod, height: &FastDivMod) -> Vec3<usize> {
+    let (row, x) = width.div_mod(index() as u32);
+    let (z, y) = height.div_mod(row);
+    Vec3::new(x as usize, y as usize, z as usize)
+}
+
 /// Whether this is the first thread (not the first thread to be executing). This function is guaranteed
 /// to only return true in a single thread that is invoking it. This is useful for only doing something
 /// once.
@@ -204,10 +205,13 @@ pub fn first() -> bool {
     block_idx() == Vec3::zero() && thread_idx() == Vec3::zero()
 }
 
-/// Gets the number of threads inside of a warp. Currently 32 threads on every GPU architecture.
+/// Gets the number of threads inside of a warp (32 on NVPTX, 64 on AMDGPU).
+///
+/// This is a runtime query rather than a compile-time constant specifically so that code
+/// using it can't silently bake in an assumption that it's always 32.
 #[inline(always)]
 pub fn warp_size() -> usize {
-    unsafe { __nvvm_warp_size() as usize }
+    unsafe { backend::warp_size() as usize }
 }
 
 /// Waits until all threads in the thread block have reached this point. This guarantees
@@ -218,7 +222,7 @@ pub fn warp_size() -> usize {
 /// or produce odd results (but should not produce undefined behavior).
 #[inline(always)]
 pub fn sync_threads() {
-    unsafe { __nvvm_block_barrier() }
+    unsafe { backend::block_barrier() }
 }
 
 /// Identical to [`sync_threads`] but with the additional feature that it evaluates
@@ -267,19 +271,19 @@ pub fn sync_threads_or(predicate: u32) -> u32 {
 /// to sync threads at a grid level. It is simply a memory fence.
 #[inline(always)]
 pub fn grid_fence() {
-    unsafe { __nvvm_grid_fence() }
+    unsafe { backend::grid_fence() }
 }
 
 /// Acts as a memory fence at the device level.
 #[inline(always)]
 pub fn device_fence() {
-    unsafe { __nvvm_device_fence() }
+    unsafe { backend::device_fence() }
 }
 
 /// Acts as a memory fence at the system level.
 #[inline(always)]
 pub fn system_fence() {
-    unsafe { __nvvm_system_fence() }
+    unsafe { backend::system_fence() }
 }
 
 /// Suspends the calling thread for a duration (in nanoseconds) approximately close to `nanos`.
@@ -0,0 +1,163 @@
+//! Vectorized, coalesced memory access helpers.
+//!
+//! Moving several contiguous elements per thread in a single, wider transaction is the
+//! standard trick for maximizing memory throughput in index-driven kernels: a thread that
+//! reads/writes one element at a time leaves most of a memory transaction's bandwidth on
+//! the table, while reading/writing a wider chunk (e.g. a `float4`) uses it fully.
+//!
+//! Pair this with [`grid_stride`] to process several contiguous elements per thread on
+//! every grid-stride iteration:
+//!
+//! ```ignore
+//! for i in grid_stride(len / 4) {
+//!     let v = unsafe { load_vectorized::<f32, 4>(input, len, i) };
+//!     // ... operate on `v` ...
+//!     unsafe { store_vectorized(output, len, i, v) };
+//! }
+//! ```
+
+/// An `N`-lane vector of `T`, meant to be moved in a single, aligned memory transaction.
+///
+/// Only total widths (`size_of::<T>() * N`) of 2, 4, 8 or 16 bytes are supported, since
+/// those are the widths the hardware can actually issue a single load/store for; using
+/// any other combination of `T`/`N` is a compile error.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct VectorType<T: Copy, const N: usize> {
+    pub lanes: [T; N],
+}
+
+impl<T: Copy, const N: usize> VectorType<T, N> {
+    const ASSERT_SUPPORTED_WIDTH: () = {
+        let bytes = core::mem::size_of::<T>() * N;
+        assert!(
+            bytes == 2 || bytes == 4 || bytes == 8 || bytes == 16,
+            "VectorType is only valid for 2/4/8/16-byte-wide vectors"
+        );
+    };
+
+    /// Loads a `VectorType` starting at `ptr` in a single transaction.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be valid for reads of `size_of::<Self>()` bytes and aligned to
+    /// `size_of::<Self>()`.
+    #[inline(always)]
+    pub unsafe fn load(ptr: *const T) -> Self {
+        let () = Self::ASSERT_SUPPORTED_WIDTH;
+        debug_assert_eq!(ptr as usize % core::mem::size_of::<Self>(), 0);
+        (ptr as *const Self).read()
+    }
+
+    /// Stores a `VectorType` starting at `ptr` in a single transaction.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be valid for writes of `size_of::<Self>()` bytes and aligned to
+    /// `size_of::<Self>()`.
+    #[inline(always)]
+    pub unsafe fn store(self, ptr: *mut T) {
+        let () = Self::ASSERT_SUPPORTED_WIDTH;
+        debug_assert_eq!(ptr as usize % core::mem::size_of::<Self>(), 0);
+        (ptr as *mut Self).write(self)
+    }
+}
+
+/// Loads the `i`-th `N`-wide chunk of `T` starting at `base`, as a single vectorized
+/// transaction when the chunk fully fits within the first `len` elements of `base`, or
+/// falling back to one scalar load per lane for the ragged tail at the end of the buffer.
+///
+/// # Safety
+///
+/// `base` must be valid for reads of `len` elements, and `base` itself must be aligned to
+/// `size_of::<VectorType<T, N>>()`.
+#[inline(always)]
+pub unsafe fn load_vectorized<T: Copy + Default, const N: usize>(
+    base: *const T,
+    len: usize,
+    i: usize,
+) -> [T; N] {
+    let start = i * N;
+    if start + N <= len {
+        VectorType::<T, N>::load(base.add(start)).lanes
+    } else {
+        let mut out = [T::default(); N];
+        for (lane, slot) in out.iter_mut().enumerate() {
+            if start + lane < len {
+                *slot = *base.add(start + lane);
+            }
+        }
+        out
+    }
+}
+
+/// Stores `values` as the `i`-th `N`-wide chunk of `T` starting at `base`, as a single
+/// vectorized transaction when the chunk fully fits within the first `len` elements of
+/// `base`, or falling back to one scalar store per lane for the ragged tail at the end of
+/// the buffer.
+///
+/// # Safety
+///
+/// `base` must be valid for writes of `len` elements, and `base` itself must be aligned to
+/// `size_of::<VectorType<T, N>>()`.
+#[inline(always)]
+pub unsafe fn store_vectorized<T: Copy, const N: usize>(
+    base: *mut T,
+    len: usize,
+    i: usize,
+    values: [T; N],
+) {
+    let start = i * N;
+    if start + N <= len {
+        VectorType { lanes: values }.store(base.add(start));
+    } else {
+        for (lane, value) in values.into_iter().enumerate() {
+            if start + lane < len {
+                *base.add(start + lane) = value;
+            }
+        }
+    }
+}
+
+/// A grid-stride loop over `0..len`, advancing by `grid_dim() * block_dim()` each step.
+///
+/// This is the standard pattern for writing a kernel once that stays correct no matter
+/// how many blocks/threads it ends up being launched with: a single thread processes
+/// `len / (grid_dim() * block_dim())` elements (rounded up) instead of exactly one.
+///
+/// ```ignore
+/// for i in grid_stride(len) {
+///     data[i] *= 2.0;
+/// }
+/// ```
+pub struct GridStride {
+    next: usize,
+    stride: usize,
+    len: usize,
+}
+
+impl Iterator for GridStride {
+    type Item = usize;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<usize> {
+        if self.next < self.len {
+            let cur = self.next;
+            self.next += self.stride;
+            Some(cur)
+        } else {
+            None
+        }
+    }
+}
+
+/// Creates a [`GridStride`] iterating `0..len`, starting at this thread's global
+/// [`super::index`] and advancing by `grid_dim() * block_dim()` each step.
+#[inline(always)]
+pub fn grid_stride(len: usize) -> GridStride {
+    GridStride {
+        next: super::index(),
+        stride: super::grid_dim().product() * super::block_dim().product(),
+        len,
+    }
+}
@@ -0,0 +1,261 @@
+//! Warp-level primitives for cross-lane communication.
+//!
+//! A warp is a group of consecutively-numbered threads (see [`super::warp_size`]) that
+//! execute in lockstep on the hardware. The intrinsics in this module let threads inside
+//! the same warp exchange data and vote on predicates directly through registers, without
+//! going through shared or global memory.
+//!
+//! Every `_sync` primitive here takes an explicit 32-bit mask of the lanes that are
+//! participating in the call, exactly like the CUDA `_sync` intrinsics require on Volta
+//! and newer architectures. If every active lane is participating, [`active_mask`] can be
+//! used to obtain this mask.
+//!
+//! The vote/shuffle instructions wrapped here are NVPTX-specific (they have no AMDGCN
+//! equivalent), so unlike the rest of the thread model, this module does not work on the
+//! AMDGPU backend. [`warp_reduce`] and [`warp_inclusive_scan`] do read [`super::warp_size`]
+//! at runtime rather than assuming 32, so they at least adapt to the warp width of
+//! whichever NVPTX target they're compiled for.
+
+use cuda_std_macros::gpu_only;
+
+extern "C" {
+    #[link_name = "llvm.nvvm.read.ptx.sreg.laneid"]
+    fn __nvvm_lane_id() -> u32;
+
+    #[link_name = "llvm.nvvm.activemask"]
+    fn __nvvm_activemask() -> u32;
+
+    #[link_name = "llvm.nvvm.vote.all.sync"]
+    fn __nvvm_vote_all_sync(mask: u32, pred: bool) -> bool;
+
+    #[link_name = "llvm.nvvm.vote.any.sync"]
+    fn __nvvm_vote_any_sync(mask: u32, pred: bool) -> bool;
+
+    #[link_name = "llvm.nvvm.vote.ballot.sync"]
+    fn __nvvm_vote_ballot_sync(mask: u32, pred: bool) -> u32;
+
+    #[link_name = "llvm.nvvm.shfl.sync.idx.i32"]
+    fn __nvvm_shfl_sync_idx(mask: u32, val: u32, src_lane: u32, packed_width: u32) -> u32;
+
+    #[link_name = "llvm.nvvm.shfl.sync.up.i32"]
+    fn __nvvm_shfl_sync_up(mask: u32, val: u32, delta: u32, packed_width: u32) -> u32;
+
+    #[link_name = "llvm.nvvm.shfl.sync.down.i32"]
+    fn __nvvm_shfl_sync_down(mask: u32, val: u32, delta: u32, packed_width: u32) -> u32;
+
+    #[link_name = "llvm.nvvm.shfl.sync.bfly.i32"]
+    fn __nvvm_shfl_sync_bfly(mask: u32, val: u32, lane_mask: u32, packed_width: u32) -> u32;
+
+    #[link_name = "llvm.nvvm.match.any.sync.i32"]
+    fn __nvvm_match_any_sync(mask: u32, val: u32) -> u32;
+}
+
+/// The lane index of the calling thread within its warp, in `0..32`.
+#[gpu_only]
+#[inline(always)]
+pub fn lane_id() -> u32 {
+    unsafe { __nvvm_lane_id() }
+}
+
+/// A mask of all lanes in the calling thread's warp that are currently active
+/// (i.e. have not exited or diverged away from this point in the control flow).
+///
+/// This is the mask to pass to the other `_sync` primitives in this module when every
+/// currently-active lane should participate.
+#[gpu_only]
+#[inline(always)]
+pub fn active_mask() -> u32 {
+    unsafe { __nvvm_activemask() }
+}
+
+/// The full warp mask, used as the default participation mask by the reduction helpers
+/// in this module when every lane of the warp is known to be active.
+pub const FULL_MASK: u32 = 0xFFFFFFFF;
+
+/// Evaluates `pred` across every lane named in `mask` and returns `true` if it was
+/// non-zero for **all** of them.
+#[gpu_only]
+#[inline(always)]
+pub fn all_sync(mask: u32, pred: bool) -> bool {
+    unsafe { __nvvm_vote_all_sync(mask, pred) }
+}
+
+/// Evaluates `pred` across every lane named in `mask` and returns `true` if it was
+/// non-zero for **any** of them.
+#[gpu_only]
+#[inline(always)]
+pub fn any_sync(mask: u32, pred: bool) -> bool {
+    unsafe { __nvvm_vote_any_sync(mask, pred) }
+}
+
+/// Evaluates `pred` across every lane named in `mask` and returns a bitmask with bit `i`
+/// set if lane `i` evaluated `pred` to non-zero.
+#[gpu_only]
+#[inline(always)]
+pub fn ballot_sync(mask: u32, pred: bool) -> u32 {
+    unsafe { __nvvm_vote_ballot_sync(mask, pred) }
+}
+
+/// Directly copies `val` from lane `src_lane` (taken modulo the warp size) to every
+/// lane named in `mask`.
+#[gpu_only]
+#[inline(always)]
+pub fn shfl_sync(mask: u32, val: u32, src_lane: u32) -> u32 {
+    unsafe { __nvvm_shfl_sync_idx(mask, val, src_lane, 0x1f) }
+}
+
+/// Copies `val` from the lane whose id is `delta` lower than the caller's, within the
+/// same warp. Lanes with no valid source (`lane_id() < delta`) keep their own `val`.
+#[gpu_only]
+#[inline(always)]
+pub fn shfl_up_sync(mask: u32, val: u32, delta: u32) -> u32 {
+    unsafe { __nvvm_shfl_sync_up(mask, val, delta, 0) }
+}
+
+/// Copies `val` from the lane whose id is `delta` higher than the caller's, within the
+/// same warp. Lanes with no valid source (`lane_id() + delta >= warp_size()`) keep their
+/// own `val`.
+#[gpu_only]
+#[inline(always)]
+pub fn shfl_down_sync(mask: u32, val: u32, delta: u32) -> u32 {
+    unsafe { __nvvm_shfl_sync_down(mask, val, delta, 0x1f) }
+}
+
+/// Copies `val` from the lane whose id is the caller's lane id XOR'd with `lane_mask`,
+/// i.e. a butterfly exchange. This is the primitive behind the classic warp-reduce
+/// butterfly loop.
+#[gpu_only]
+#[inline(always)]
+pub fn shfl_xor_sync(mask: u32, val: u32, lane_mask: u32) -> u32 {
+    unsafe { __nvvm_shfl_sync_bfly(mask, val, lane_mask, 0x1f) }
+}
+
+/// Compares `val` across every lane named in `mask` and returns a bitmask of the lanes
+/// that hold the same `val` as the caller (the caller's own bit is always set).
+#[gpu_only]
+#[inline(always)]
+pub fn match_any_sync(mask: u32, val: u32) -> u32 {
+    unsafe { __nvvm_match_any_sync(mask, val) }
+}
+
+/// A value that can be moved between lanes of a warp with `shfl_sync` and friends, by
+/// round-tripping through its `u32` bit representation.
+///
+/// This is implemented for the primitive 32-bit types that the `shfl.sync` instructions
+/// natively support; wider types need to be shuffled one `u32` chunk at a time.
+pub trait WarpShuffle: Copy {
+    fn to_warp_bits(self) -> u32;
+    fn from_warp_bits(bits: u32) -> Self;
+}
+
+macro_rules! impl_warp_shuffle {
+    ($($ty:ty),*) => {
+        $(
+            impl WarpShuffle for $ty {
+                #[inline(always)]
+                fn to_warp_bits(self) -> u32 {
+                    self as u32
+                }
+
+                #[inline(always)]
+                fn from_warp_bits(bits: u32) -> Self {
+                    bits as Self
+                }
+            }
+        )*
+    };
+}
+
+impl_warp_shuffle!(u32, i32);
+
+impl WarpShuffle for f32 {
+    #[inline(always)]
+    fn to_warp_bits(self) -> u32 {
+        self.to_bits()
+    }
+
+    #[inline(always)]
+    fn from_warp_bits(bits: u32) -> Self {
+        f32::from_bits(bits)
+    }
+}
+
+/// Reduces `val` across every lane named in `mask` using `op`. The final, fully-reduced
+/// result is only guaranteed to end up in the lowest-ranked lane of `mask` (i.e. the lane
+/// `mask.trailing_zeros()`) - other lanes are left holding an unspecified partial value.
+///
+/// `op` must be commutative and associative. `mask` does not need to be contiguous or
+/// power-of-two-sized: lanes are paired up by their *rank* within `mask` (rather than their
+/// raw lane id) and folded towards rank `0`, since butterflying on raw lane ids - with
+/// either [`shfl_xor_sync`] or a fixed-offset [`shfl_down_sync`] walk - would `shfl` from a
+/// lane outside `mask` whenever `mask` is sparse, which is undefined per the `_sync`
+/// contract. This is a deliberate departure from the fixed `[16, 8, 4, 2, 1]`
+/// `shfl_down_sync` offsets of a textbook full-warp reduction: that walk only produces a
+/// correct result when every lane of the warp participates, which would make
+/// [`grid::grid_reduce`](super::grid::grid_reduce)'s representative-lane mode unsound.
+/// Every lane named in `mask` still executes the same `shfl_sync` call on every step
+/// (reading its own value back when it isn't combining that step), since that's what the
+/// hardware's lockstep convergence requirement demands.
+#[gpu_only]
+#[inline(always)]
+pub fn warp_reduce<T: WarpShuffle>(mask: u32, mut val: T, mut op: impl FnMut(T, T) -> T) -> T {
+    let popcount = mask.count_ones();
+    if popcount <= 1 {
+        return val;
+    }
+
+    let my_lane = lane_id();
+    let my_rank = (mask & ((1u32 << my_lane) - 1)).count_ones();
+
+    let steps = 32 - (popcount - 1).leading_zeros();
+    for step in 0..steps {
+        let span = 1 << step;
+        let is_accumulator = my_rank % (span * 2) == 0;
+        let partner_rank = my_rank + span;
+        let combines = is_accumulator && partner_rank < popcount;
+
+        let partner_lane = if combines {
+            nth_set_bit(mask, partner_rank)
+        } else {
+            my_lane
+        };
+
+        let other = T::from_warp_bits(shfl_sync(mask, val.to_warp_bits(), partner_lane));
+        if combines {
+            val = op(val, other);
+        }
+    }
+    val
+}
+
+/// Returns the lane id of the `n`-th (0-indexed) set bit of `mask`, counting from the
+/// lowest bit. Used by [`warp_reduce`] to turn a rank within `mask` back into a lane id.
+#[inline(always)]
+fn nth_set_bit(mask: u32, n: u32) -> u32 {
+    let mut remaining = mask;
+    for _ in 0..n {
+        remaining &= remaining - 1;
+    }
+    remaining.trailing_zeros()
+}
+
+/// Computes an inclusive prefix scan of `val` across the lanes named in `mask`, i.e.
+/// lane `i` ends up holding `val(0) op val(1) op .. op val(i)`.
+///
+/// `op` must be associative. Built on top of [`shfl_up_sync`] using the classic
+/// Hillis-Steele doubling-distance scan.
+#[gpu_only]
+#[inline(always)]
+pub fn warp_inclusive_scan<T: WarpShuffle>(mut val: T, mut op: impl FnMut(T, T) -> T) -> T {
+    let lane = lane_id();
+    let warp_size = super::warp_size() as u32;
+    let mut delta = 1;
+    while delta < warp_size {
+        let shuffled = shfl_up_sync(FULL_MASK, val.to_warp_bits(), delta);
+        if lane >= delta {
+            val = op(val, T::from_warp_bits(shuffled));
+        }
+        delta *= 2;
+    }
+    val
+}